@@ -41,12 +41,12 @@ fn main() {
     let root = match load_file(&config_path) {
         Ok(r) => r,
         Err(e) => {
-            eprintln!("error loading config: {}", e);
+            eprintln!("error loading config:\n{}", e.render());
             process::exit(1);
         }
     };
 
-    if let Some(schema_path) = schema_path {
+    let root = if let Some(schema_path) = schema_path {
         let schema = match load_schema(&schema_path) {
             Ok(s) => s,
             Err(e) => {
@@ -54,11 +54,16 @@ fn main() {
                 process::exit(1);
             }
         };
-        if let Err(e) = validate(&root, &schema) {
-            eprintln!("validation error: {}", e);
-            process::exit(1);
+        match validate(&root, &schema) {
+            Ok(augmented) => augmented,
+            Err(e) => {
+                eprintln!("validation error: {}", e);
+                process::exit(1);
+            }
         }
-    }
+    } else {
+        root
+    };
 
     println!("{{");
     let n = root.len();
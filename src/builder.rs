@@ -0,0 +1,190 @@
+//! Layered configuration: merge several sources into one `Value` tree.
+//!
+//! Sources are applied in the order they were added, with later sources
+//! overriding earlier ones. Unlike a whole-key replace, nested `Value::Map`
+//! trees are deep-merged key by key, so a base file can set `log.file` and a
+//! later env var can override just `log.level` without losing `log.file`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{load_file, parse_str, set_nested, LoadError, ParseError, Value};
+
+enum Source {
+    File(PathBuf),
+    Str(String),
+    Env { prefix: String, separator: String },
+}
+
+/// Collects config sources (files, inline strings, env vars) and merges them
+/// into one root map, last-source-wins.
+#[derive(Default)]
+pub struct ConfigBuilder {
+    sources: Vec<Source>,
+}
+
+impl ConfigBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        ConfigBuilder::default()
+    }
+
+    /// Adds a sysctl.conf-style file as a source.
+    pub fn add_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File(path.into()));
+        self
+    }
+
+    /// Adds an inline sysctl.conf-style string as a source.
+    pub fn add_str(mut self, input: impl Into<String>) -> Self {
+        self.sources.push(Source::Str(input.into()));
+        self
+    }
+
+    /// Adds environment variables starting with `prefix` as a source.
+    /// The remainder of each variable name is lowercased and split on
+    /// `separator` to build a dotted path, e.g. prefix `"MYAPP_"` and
+    /// separator `"__"` turns `MYAPP_LOG__FILE=/tmp/x` into `log.file = /tmp/x`.
+    pub fn add_env(mut self, prefix: impl Into<String>, separator: impl Into<String>) -> Self {
+        self.sources.push(Source::Env {
+            prefix: prefix.into(),
+            separator: separator.into(),
+        });
+        self
+    }
+
+    /// Loads every source in order and deep-merges them into one root map.
+    pub fn build(self) -> Result<HashMap<String, Value>, BuilderError> {
+        let mut root: HashMap<String, Value> = HashMap::new();
+        for source in self.sources {
+            let layer = match source {
+                Source::File(path) => {
+                    load_file(&path).map_err(|e| BuilderError::File { path, source: e })?
+                }
+                Source::Str(input) => parse_str(&input).map_err(BuilderError::Str)?,
+                Source::Env { prefix, separator } => env_layer(&prefix, &separator),
+            };
+            deep_merge(&mut root, layer);
+        }
+        Ok(root)
+    }
+}
+
+/// Builds a nested map from the process environment, keeping only variables
+/// whose name starts with `prefix`.
+fn env_layer(prefix: &str, separator: &str) -> HashMap<String, Value> {
+    let mut root = HashMap::new();
+    for (key, value) in env::vars() {
+        let Some(rest) = key.strip_prefix(prefix) else {
+            continue;
+        };
+        if rest.is_empty() {
+            continue;
+        }
+        let dotted = rest.to_lowercase().replace(separator, ".");
+        set_nested(&mut root, &dotted, Value::String(value));
+    }
+    root
+}
+
+/// Merges `src` into `dst`, recursing into matching `Value::Map` pairs.
+/// Leaf values (and map/leaf mismatches) are replaced outright, with `src`
+/// winning.
+pub(crate) fn deep_merge(dst: &mut HashMap<String, Value>, src: HashMap<String, Value>) {
+    for (key, value) in src {
+        match (dst.get_mut(&key), value) {
+            (Some(Value::Map(dst_map)), Value::Map(src_map)) => {
+                deep_merge(dst_map, src_map);
+            }
+            (_, value) => {
+                dst.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Error building a layered config.
+#[derive(Debug)]
+pub enum BuilderError {
+    File { path: PathBuf, source: LoadError },
+    Str(ParseError),
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderError::File { path, source } => write!(f, "{}: {}", path.display(), source),
+            BuilderError::Str(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_source_overrides_earlier_leaf() {
+        let root = ConfigBuilder::new()
+            .add_str("endpoint = localhost:3000\nlog.file = /var/log/base.log\n")
+            .add_str("log.file = /var/log/override.log\n")
+            .build()
+            .unwrap();
+        assert_eq!(
+            root.get("endpoint"),
+            Some(&Value::String("localhost:3000".into()))
+        );
+        let Some(Value::Map(log)) = root.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(
+            log.get("file"),
+            Some(&Value::String("/var/log/override.log".into()))
+        );
+    }
+
+    #[test]
+    fn merge_is_deep_not_whole_key_replace() {
+        let root = ConfigBuilder::new()
+            .add_str("log.file = /var/log/base.log\nlog.level = info\n")
+            .add_str("log.level = debug\n")
+            .build()
+            .unwrap();
+        let Some(Value::Map(log)) = root.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(
+            log.get("file"),
+            Some(&Value::String("/var/log/base.log".into()))
+        );
+        assert_eq!(log.get("level"), Some(&Value::String("debug".into())));
+    }
+
+    #[test]
+    fn env_source_maps_separator_to_dotted_path() {
+        // SAFETY: test-only; the variable name is unique to this test, and the
+        // other tests in this crate reach the environment only through
+        // `std::env`, which serializes its own reads and writes.
+        unsafe {
+            env::set_var("SYSCTL_CONF_BUILDER_TEST_LOG__FILE", "/tmp/from-env.log");
+        }
+        let root = ConfigBuilder::new()
+            .add_env("SYSCTL_CONF_BUILDER_TEST_", "__")
+            .build()
+            .unwrap();
+        unsafe {
+            env::remove_var("SYSCTL_CONF_BUILDER_TEST_LOG__FILE");
+        }
+        let Some(Value::Map(log)) = root.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(
+            log.get("file"),
+            Some(&Value::String("/tmp/from-env.log".into()))
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Serde integration: deserialize typed Rust structs from a `Value` tree.
+//!
+//! Every leaf in a parsed config is a `Value::String`, so the `Deserializer`
+//! impl below coerces on demand: booleans accept `true/false/1/0/yes/no`,
+//! integers and floats are parsed from the string, and `Value::Map` drives
+//! `deserialize_map`/`deserialize_struct`. This lets callers go straight from
+//! a parsed config to `let cfg: MyConfig = sysctl_conf::from_value(root)?`
+//! instead of hand-walking the `HashMap`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Error as _, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::{parse_str, Value};
+
+/// Deserializes a `T` from an already-parsed config map, i.e. the root
+/// returned by [`crate::parse_str`]/[`crate::load_file`].
+pub fn from_value<T>(root: HashMap<String, Value>) -> Result<T, DeError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    T::deserialize(Value::Map(root))
+}
+
+/// Parses `input` as a sysctl.conf-style string and deserializes it into `T`.
+pub fn from_str<T>(input: &str) -> Result<T, DeError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let root = parse_str(input).map_err(|e| DeError::Message(e.to_string()))?;
+    from_value(root)
+}
+
+/// Error deserializing a `Value` tree into a typed struct.
+#[derive(Debug)]
+pub enum DeError {
+    Message(String),
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::Message(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError::Message(msg.to_string())
+    }
+}
+
+impl Value {
+    fn as_leaf(&self) -> Result<&str, DeError> {
+        match self {
+            Value::String(s) => Ok(s),
+            Value::Map(_) => Err(DeError::custom("expected a leaf value, found a nested map")),
+        }
+    }
+}
+
+fn parse_bool(raw: &str) -> Result<bool, DeError> {
+    match raw.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" => Ok(true),
+        "false" | "0" | "no" => Ok(false),
+        other => Err(DeError::custom(format!("not a valid bool: '{}'", other))),
+    }
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let raw = self.as_leaf()?;
+            let n: $ty = raw.trim().parse().map_err(|_| {
+                DeError::custom(format!("not a valid {}: '{}'", stringify!($ty), raw))
+            })?;
+            visitor.$visit(n)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::String(s) => visitor.visit_string(s),
+            Value::Map(m) => visitor.visit_map(MapDeserializer::new(m)),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bool(parse_bool(self.as_leaf()?)?)
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64);
+    deserialize_number!(deserialize_f32, visit_f32, f32);
+    deserialize_number!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.as_leaf()?.to_string())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Map(m) => visitor.visit_map(MapDeserializer::new(m)),
+            Value::String(_) => Err(DeError::custom("expected a map, found a leaf value")),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any string
+    }
+}
+
+/// Drives serde's `MapAccess` over a `Value::Map`'s entries.
+struct MapDeserializer {
+    iter: std::collections::hash_map::IntoIter<String, Value>,
+    value: Option<Value>,
+}
+
+impl MapDeserializer {
+    fn new(map: HashMap<String, Value>) -> Self {
+        MapDeserializer {
+            iter: map.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(k.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Log {
+        file: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        endpoint: String,
+        debug: bool,
+        retry: i64,
+        log: Log,
+    }
+
+    #[test]
+    fn from_str_builds_nested_struct() {
+        let input = r#"
+endpoint = localhost:3000
+debug = true
+retry = 3
+log.file = /var/log/console.log
+"#;
+        let got: Config = from_str(input).unwrap();
+        assert_eq!(
+            got,
+            Config {
+                endpoint: "localhost:3000".into(),
+                debug: true,
+                retry: 3,
+                log: Log {
+                    file: "/var/log/console.log".into(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn bool_accepts_yes_no() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Flags {
+            a: bool,
+            b: bool,
+        }
+        let input = "a = yes\nb = no\n";
+        let got: Flags = from_str(input).unwrap();
+        assert_eq!(got, Flags { a: true, b: false });
+    }
+
+    #[test]
+    fn invalid_integer_is_an_error() {
+        #[derive(Debug, Deserialize)]
+        struct Retry {
+            #[allow(dead_code)]
+            retry: i64,
+        }
+        let input = "retry = not-a-number\n";
+        assert!(from_str::<Retry>(input).is_err());
+    }
+}
@@ -4,12 +4,26 @@
 //! - `key = value` (leading/trailing whitespace trimmed)
 //! - Blank lines and lines starting with `#` or `;` are ignored
 //! - A leading `-` means "ignore failure" (the `-` is stripped and the line is parsed)
+//! - `@include <path>` loads and deep-merges another file at that point, with
+//!   later keys (from this file or further includes) overriding earlier ones;
+//!   the path is resolved relative to the including file's directory
 //!
 //! Dot notation in keys creates nested maps: `log.file = path` → `log: { file: "path" }`.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+mod builder;
+mod de;
+mod query;
+pub mod schema;
+
+pub use builder::{BuilderError, ConfigBuilder};
+pub use de::{from_str, from_value, DeError};
+pub use query::{query, QueryError};
+pub use schema::{load_schema, validate};
 
 /// A value is either a leaf string or a nested map (for dot-notation keys).
 #[derive(Debug, Clone, PartialEq)]
@@ -20,11 +34,46 @@ pub enum Value {
 
 /// Parses a sysctl.conf-style string and returns a top-level map.
 /// Nested keys (e.g. `log.file`) are stored as nested maps.
+///
+/// `@include` paths are resolved relative to the current directory, since a
+/// bare string has no file of its own to be relative to. Use [`load_file`]
+/// to resolve includes relative to a file on disk.
 pub fn parse_str(input: &str) -> Result<HashMap<String, Value>, ParseError> {
+    let mut stack = HashSet::new();
+    parse_with_includes(input, Path::new("."), &mut stack)
+}
+
+/// Loads and parses a file, honoring `@include` directives relative to the
+/// file's directory. Returns the same structure as `parse_str`.
+pub fn load_file(path: impl AsRef<Path>) -> Result<HashMap<String, Value>, LoadError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(LoadError::Io)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut stack = HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(path) {
+        stack.insert(canonical);
+    }
+
+    parse_with_includes(&content, base_dir, &mut stack).map_err(|error| LoadError::Parse {
+        error,
+        source: content,
+    })
+}
+
+/// Parses `input`, honoring `@include` directives resolved against `base_dir`.
+/// `stack` holds the canonicalized paths of files currently being parsed (an
+/// ancestor chain, not a "files already seen" set), so unrelated files may
+/// include the same fragment without tripping cycle detection.
+fn parse_with_includes(
+    input: &str,
+    base_dir: &Path,
+    stack: &mut HashSet<PathBuf>,
+) -> Result<HashMap<String, Value>, ParseError> {
     let mut root: HashMap<String, Value> = HashMap::new();
 
-    for (line_num, line) in input.lines().enumerate() {
-        let line = line.trim();
+    for (line_num, raw_line) in input.lines().enumerate() {
+        let line = raw_line.trim();
         if line.is_empty() {
             continue;
         }
@@ -37,9 +86,28 @@ pub fn parse_str(input: &str) -> Result<HashMap<String, Value>, ParseError> {
             continue;
         }
 
+        if let Some(rest) = line.strip_prefix("@include") {
+            let path_str = rest.trim();
+            if path_str.is_empty() {
+                let start = byte_offset(raw_line, rest);
+                return Err(ParseError::Syntax {
+                    line: line_num + 1,
+                    span: start..start,
+                    message: "@include requires a path".into(),
+                });
+            }
+            let start = byte_offset(raw_line, path_str);
+            let span = start..start + path_str.len();
+            let included = resolve_include(base_dir, path_str, stack, line_num + 1, span)?;
+            builder::deep_merge(&mut root, included);
+            continue;
+        }
+
         let Some((key_part, value_part)) = line.split_once('=') else {
-            return Err(ParseError {
+            let start = byte_offset(raw_line, line);
+            return Err(ParseError::Syntax {
                 line: line_num + 1,
+                span: start..start + line.len(),
                 message: "missing '='".into(),
             });
         };
@@ -48,8 +116,10 @@ pub fn parse_str(input: &str) -> Result<HashMap<String, Value>, ParseError> {
         let value = value_part.trim();
 
         if key.is_empty() {
-            return Err(ParseError {
+            let start = byte_offset(raw_line, key_part);
+            return Err(ParseError::Syntax {
                 line: line_num + 1,
+                span: start..start + key_part.len(),
                 message: "empty key".into(),
             });
         }
@@ -60,14 +130,64 @@ pub fn parse_str(input: &str) -> Result<HashMap<String, Value>, ParseError> {
     Ok(root)
 }
 
-/// Loads and parses a file. Returns the same structure as `parse_str`.
-pub fn load_file(path: impl AsRef<Path>) -> Result<HashMap<String, Value>, LoadError> {
-    let content = fs::read_to_string(path.as_ref()).map_err(LoadError::Io)?;
-    parse_str(&content).map_err(LoadError::Parse)
+/// Loads and parses the file `path_str` (resolved against `base_dir`),
+/// pushing its canonicalized path onto `stack` for the duration of the
+/// recursive parse so cycles back to an ancestor are rejected.
+fn resolve_include(
+    base_dir: &Path,
+    path_str: &str,
+    stack: &mut HashSet<PathBuf>,
+    line: usize,
+    span: Range<usize>,
+) -> Result<HashMap<String, Value>, ParseError> {
+    let requested = base_dir.join(path_str);
+
+    let canonical = fs::canonicalize(&requested).map_err(|e| ParseError::Include {
+        line,
+        span: span.clone(),
+        path: requested.clone(),
+        cause: Box::new(IncludeErrorCause::Io(e)),
+    })?;
+
+    if !stack.insert(canonical.clone()) {
+        return Err(ParseError::Include {
+            line,
+            span,
+            path: requested,
+            cause: Box::new(IncludeErrorCause::Cycle),
+        });
+    }
+
+    let content = fs::read_to_string(&canonical).map_err(|e| ParseError::Include {
+        line,
+        span: span.clone(),
+        path: requested.clone(),
+        cause: Box::new(IncludeErrorCause::Io(e)),
+    })?;
+    let included_base = canonical.parent().unwrap_or_else(|| Path::new("."));
+    let result = parse_with_includes(&content, included_base, stack);
+    stack.remove(&canonical);
+
+    result.map_err(|error| ParseError::Include {
+        line,
+        span,
+        path: requested,
+        cause: Box::new(IncludeErrorCause::Parse {
+            error: Box::new(error),
+            source: content,
+        }),
+    })
+}
+
+/// Returns `sub`'s byte offset within `origin`. `sub` must be a subslice of
+/// `origin` (as produced by `trim`/`strip_prefix`/`split_once`, which never
+/// copy), so this is pointer arithmetic, not a search.
+fn byte_offset(origin: &str, sub: &str) -> usize {
+    sub.as_ptr() as usize - origin.as_ptr() as usize
 }
 
 /// Sets a possibly dotted key into a nested map. Creates intermediate maps as needed.
-fn set_nested(root: &mut HashMap<String, Value>, key: &str, value: Value) {
+pub(crate) fn set_nested(root: &mut HashMap<String, Value>, key: &str, value: Value) {
     let parts: Vec<&str> = key.split('.').map(str::trim).collect();
     if parts.is_empty() {
         return;
@@ -121,30 +241,154 @@ fn set_nested_rest(map: &mut HashMap<String, Value>, parts: &[&str], value: Valu
 }
 
 #[derive(Debug)]
-pub struct ParseError {
-    pub line: usize,
-    pub message: String,
+pub enum ParseError {
+    /// A syntax error on a single line: missing `=`, an empty key, or a
+    /// malformed `@include` directive.
+    Syntax {
+        line: usize,
+        /// Byte range of the offending span within that line's source text.
+        span: Range<usize>,
+        message: String,
+    },
+    /// An `@include` directive failed: the referenced file couldn't be read,
+    /// failed to parse itself, or would re-enter a file already on the
+    /// include stack.
+    Include {
+        line: usize,
+        /// Byte range of the include path within that line's source text.
+        span: Range<usize>,
+        path: PathBuf,
+        cause: Box<IncludeErrorCause>,
+    },
+}
+
+impl ParseError {
+    /// The 1-based line number the error occurred on.
+    pub fn line(&self) -> usize {
+        match self {
+            ParseError::Syntax { line, .. } => *line,
+            ParseError::Include { line, .. } => *line,
+        }
+    }
+
+    /// Byte range of the offending span within that line's source text.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ParseError::Syntax { span, .. } => span.clone(),
+            ParseError::Include { span, .. } => span.clone(),
+        }
+    }
+
+    /// Renders an annotated snippet: the offending source line, a caret
+    /// underline under the error's span, and the message.
+    ///
+    /// `source` must be the text that was parsed to produce this error. If
+    /// the error actually originates inside an `@include`d file, the
+    /// fragment's own source (captured at the time the include failed) is
+    /// used to render its line/caret instead of `source` — so a syntax error
+    /// several `@include`s deep still points at its real location rather
+    /// than just the top-level `@include` line.
+    pub fn render(&self, source: &str) -> String {
+        if let ParseError::Include { cause, .. } = self {
+            if let IncludeErrorCause::Parse {
+                error,
+                source: included_source,
+            } = cause.as_ref()
+            {
+                return format!(
+                    "line {}: {}\n{}",
+                    self.line(),
+                    self.message(),
+                    error.render(included_source)
+                );
+            }
+        }
+
+        let line = self.line();
+        let Some(source_line) = source.lines().nth(line - 1) else {
+            return self.to_string();
+        };
+        let span = self.span();
+        let start = span.start.min(source_line.len());
+        let end = span.end.min(source_line.len()).max(start);
+        let underline = format!("{}{}", " ".repeat(start), "^".repeat((end - start).max(1)));
+        format!(
+            "line {}: {}\n  {}\n  {}",
+            line,
+            self.message(),
+            source_line,
+            underline
+        )
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ParseError::Syntax { message, .. } => message.clone(),
+            ParseError::Include { path, cause, .. } => {
+                format!("@include '{}': {}", path.display(), cause)
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "line {}: {}", self.line, self.message)
+        write!(f, "line {}: {}", self.line(), self.message())
     }
 }
 
 impl std::error::Error for ParseError {}
 
+/// Why an `@include` directive failed.
+#[derive(Debug)]
+pub enum IncludeErrorCause {
+    Io(std::io::Error),
+    /// The included file failed to parse. `source` is that file's own
+    /// contents, kept around so `ParseError::render` can underline `error`'s
+    /// actual line instead of just the `@include` line that pulled it in.
+    Parse {
+        error: Box<ParseError>,
+        source: String,
+    },
+    Cycle,
+}
+
+impl std::fmt::Display for IncludeErrorCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IncludeErrorCause::Io(e) => write!(f, "io: {}", e),
+            IncludeErrorCause::Parse { error, .. } => write!(f, "{}", error),
+            IncludeErrorCause::Cycle => write!(f, "include cycle detected"),
+        }
+    }
+}
+
+impl std::error::Error for IncludeErrorCause {}
+
 #[derive(Debug)]
 pub enum LoadError {
     Io(std::io::Error),
-    Parse(ParseError),
+    /// A parse failure, with the full file contents retained so the caller
+    /// can render an annotated snippet without re-reading the file.
+    Parse { error: ParseError, source: String },
+}
+
+impl LoadError {
+    /// Renders an annotated snippet for `Parse` errors; falls back to the
+    /// plain message for `Io`.
+    pub fn render(&self) -> String {
+        match self {
+            LoadError::Io(e) => format!("io: {}", e),
+            LoadError::Parse { error, source } => error.render(source),
+        }
+    }
 }
 
 impl std::fmt::Display for LoadError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             LoadError::Io(e) => write!(f, "io: {}", e),
-            LoadError::Parse(e) => write!(f, "parse: {}", e),
+            LoadError::Parse { error, .. } => write!(f, "parse: {}", error),
         }
     }
 }
@@ -224,4 +468,157 @@ other = value
         k.insert("foo".into(), Value::String("bar".into()));
         assert_eq!(got.get("kernel"), Some(&Value::Map(k)));
     }
+
+    #[test]
+    fn missing_equals_spans_the_whole_line_content() {
+        let input = "endpoint = localhost\nbroken line without equals\n";
+        let err = parse_str(input).unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(
+            &input.lines().nth(1).unwrap()[err.span()],
+            "broken line without equals"
+        );
+    }
+
+    #[test]
+    fn empty_key_spans_the_empty_key_range() {
+        let input = "= value\n";
+        let err = parse_str(input).unwrap_err();
+        assert_eq!(err.line(), 1);
+        assert_eq!(err.span(), 0..0);
+    }
+
+    #[test]
+    fn render_underlines_the_span() {
+        let input = "= value\n";
+        let err = parse_str(input).unwrap_err();
+        let rendered = err.render(input);
+        assert!(rendered.contains("empty key"));
+        assert!(rendered.contains('^'));
+    }
+
+    /// Creates a scratch directory under `std::env::temp_dir()` for a single
+    /// test, writing each `(name, content)` pair as a file inside it.
+    fn write_fixture(dir_name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sysctl_conf_test_{}", dir_name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (name, content) in files {
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn include_merges_referenced_file_at_that_point() {
+        let dir = write_fixture(
+            "include_merge",
+            &[
+                ("base.conf", "log.level = info\n"),
+                (
+                    "main.conf",
+                    "endpoint = localhost\n@include base.conf\nlog.file = /var/log/x.log\n",
+                ),
+            ],
+        );
+        let got = load_file(dir.join("main.conf")).unwrap();
+        assert_eq!(
+            got.get("endpoint"),
+            Some(&Value::String("localhost".into()))
+        );
+        let Some(Value::Map(log)) = got.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(log.get("level"), Some(&Value::String("info".into())));
+        assert_eq!(log.get("file"), Some(&Value::String("/var/log/x.log".into())));
+    }
+
+    #[test]
+    fn include_path_is_relative_to_including_file() {
+        let dir = write_fixture(
+            "include_relative",
+            &[
+                ("fragments/log.conf", "log.file = /var/log/nested.log\n"),
+                ("main.conf", "@include fragments/log.conf\n"),
+            ],
+        );
+        let got = load_file(dir.join("main.conf")).unwrap();
+        let Some(Value::Map(log)) = got.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(
+            log.get("file"),
+            Some(&Value::String("/var/log/nested.log".into()))
+        );
+    }
+
+    #[test]
+    fn later_keys_override_included_keys() {
+        let dir = write_fixture(
+            "include_override",
+            &[
+                ("base.conf", "log.level = info\n"),
+                ("main.conf", "@include base.conf\nlog.level = debug\n"),
+            ],
+        );
+        let got = load_file(dir.join("main.conf")).unwrap();
+        let Some(Value::Map(log)) = got.get("log") else {
+            panic!("expected log map");
+        };
+        assert_eq!(log.get("level"), Some(&Value::String("debug".into())));
+    }
+
+    /// Walks an `Include` error chain looking for a `Cycle` cause at any depth.
+    fn has_cycle_cause(err: &ParseError) -> bool {
+        match err {
+            ParseError::Syntax { .. } => false,
+            ParseError::Include { cause, .. } => match cause.as_ref() {
+                IncludeErrorCause::Cycle => true,
+                IncludeErrorCause::Parse { error, .. } => has_cycle_cause(error),
+                IncludeErrorCause::Io(_) => false,
+            },
+        }
+    }
+
+    #[test]
+    fn include_cycle_is_rejected() {
+        let dir = write_fixture(
+            "include_cycle",
+            &[
+                ("a.conf", "@include b.conf\n"),
+                ("b.conf", "@include a.conf\n"),
+            ],
+        );
+        let err = load_file(dir.join("a.conf")).unwrap_err();
+        let LoadError::Parse { error, .. } = err else {
+            panic!("expected Parse error");
+        };
+        assert!(has_cycle_cause(&error), "expected an Include cycle error, got {:?}", error);
+    }
+
+    #[test]
+    fn render_points_at_the_offending_line_inside_an_included_fragment() {
+        let dir = write_fixture(
+            "include_syntax_error",
+            &[
+                ("broken.conf", "ok = value\nbroken line without equals\n"),
+                ("main.conf", "endpoint = localhost\n@include broken.conf\n"),
+            ],
+        );
+        let err = load_file(dir.join("main.conf")).unwrap_err();
+        let LoadError::Parse { error, source } = &err else {
+            panic!("expected Parse error");
+        };
+        let rendered = error.render(source);
+        assert!(
+            rendered.contains("broken line without equals"),
+            "expected the fragment's own source line in the render, got:\n{}",
+            rendered
+        );
+        assert!(rendered.contains('^'));
+    }
 }
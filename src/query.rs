@@ -0,0 +1,327 @@
+//! A small query language over `Value` trees, inspired by preserves-path.
+//!
+//! A query is a sequence of `.`-separated steps applied left-to-right to a
+//! working set of nodes, starting from the root map:
+//! - `key`    — child step: descend into `Value::Map` by exact key
+//! - `*`      — wildcard step: all direct children of each node
+//! - `**`     — descendants step: each node plus everything nested under it
+//! - `[pred]` — filter step, attached directly after the step it follows;
+//!   keeps nodes whose leaf matches `= "lit"` (string equality), `~ "regex"`
+//!   (regex match), or `:type` (parses as a schema type; `|` separates
+//!   alternatives, e.g. `[:string|:integer]`)
+//!
+//! Each step maps the current node set to a new node set; the final set is
+//! returned. For example `query(&root, "log.**[~ \"\\.log$\"]")` finds every
+//! leaf under `log` whose value looks like a log file path.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use regex::Regex;
+
+use crate::schema::SchemaType;
+use crate::Value;
+
+/// Runs `q` against `root`, returning every leaf/map node the query selects.
+pub fn query<'a>(root: &'a HashMap<String, Value>, q: &str) -> Result<Vec<&'a Value>, QueryError> {
+    if q.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let steps = parse_query(q)?;
+    let mut frontier: Vec<Node<'a>> = vec![Node::Root(root)];
+    for step in &steps {
+        frontier = apply_step(frontier, step);
+    }
+    Ok(frontier.into_iter().filter_map(|n| n.as_value()).collect())
+}
+
+/// A node in the working set: either the implicit root map (which has no
+/// `Value` representation of its own) or a `Value` reached by navigating
+/// into it.
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Root(&'a HashMap<String, Value>),
+    Val(&'a Value),
+}
+
+impl<'a> Node<'a> {
+    fn as_map(&self) -> Option<&'a HashMap<String, Value>> {
+        match self {
+            Node::Root(m) => Some(m),
+            Node::Val(Value::Map(m)) => Some(m),
+            Node::Val(Value::String(_)) => None,
+        }
+    }
+
+    fn as_value(&self) -> Option<&'a Value> {
+        match self {
+            Node::Root(_) => None,
+            Node::Val(v) => Some(v),
+        }
+    }
+
+    fn get_child(&self, key: &str) -> Option<Node<'a>> {
+        self.as_map()?.get(key).map(Node::Val)
+    }
+
+    fn children(&self) -> Vec<Node<'a>> {
+        match self.as_map() {
+            Some(m) => m.values().map(Node::Val).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn collect_descendants<'a>(node: &Node<'a>, out: &mut Vec<Node<'a>>) {
+    if node.as_value().is_some() {
+        out.push(*node);
+    }
+    for child in node.children() {
+        collect_descendants(&child, out);
+    }
+}
+
+fn apply_step<'a>(frontier: Vec<Node<'a>>, step: &Step) -> Vec<Node<'a>> {
+    match step {
+        Step::Child(key) => frontier.iter().filter_map(|n| n.get_child(key)).collect(),
+        Step::Wildcard => frontier.iter().flat_map(|n| n.children()).collect(),
+        Step::Descendants => {
+            let mut out = Vec::new();
+            for node in &frontier {
+                collect_descendants(node, &mut out);
+            }
+            out
+        }
+        Step::Filter(filter) => frontier
+            .into_iter()
+            .filter(|n| n.as_value().is_some_and(|v| filter.matches(v)))
+            .collect(),
+    }
+}
+
+enum Step {
+    Child(String),
+    Wildcard,
+    Descendants,
+    Filter(Filter),
+}
+
+enum Filter {
+    Equals(String),
+    Matches(Regex),
+    Type(Vec<SchemaType>),
+}
+
+impl Filter {
+    fn matches(&self, value: &Value) -> bool {
+        let Value::String(raw) = value else {
+            return false;
+        };
+        match self {
+            Filter::Equals(lit) => raw == lit,
+            Filter::Matches(re) => re.is_match(raw),
+            Filter::Type(types) => types.iter().any(|t| t.check_value(raw)),
+        }
+    }
+}
+
+/// Splits a query string into top-level `.`-separated tokens, treating `.`
+/// inside `[...]` (including quoted string literals) as literal text.
+fn tokenize(q: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut depth = 0i32;
+    let mut in_quote = false;
+    let mut start = 0usize;
+
+    for (i, c) in q.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '[' if !in_quote => depth += 1,
+            ']' if !in_quote => depth -= 1,
+            '.' if depth == 0 && !in_quote => {
+                tokens.push(&q[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(&q[start..]);
+    tokens
+}
+
+fn parse_query(q: &str) -> Result<Vec<Step>, QueryError> {
+    let mut steps = Vec::new();
+    for token in tokenize(q) {
+        let (head, filter) = split_filter(token)?;
+        let head = head.trim();
+        if !head.is_empty() {
+            steps.push(match head {
+                "*" => Step::Wildcard,
+                "**" => Step::Descendants,
+                key => Step::Child(key.to_string()),
+            });
+        } else if filter.is_none() {
+            return Err(QueryError::Syntax(format!("empty step in '{}'", q)));
+        }
+        if let Some(filter) = filter {
+            steps.push(Step::Filter(Filter::parse(filter)?));
+        }
+    }
+    Ok(steps)
+}
+
+/// Splits a token into its navigation head and an optional `[...]` filter body.
+fn split_filter(token: &str) -> Result<(&str, Option<&str>), QueryError> {
+    let Some(open) = token.find('[') else {
+        return Ok((token, None));
+    };
+    if !token.ends_with(']') {
+        return Err(QueryError::Syntax(format!(
+            "unterminated filter in '{}'",
+            token
+        )));
+    }
+    Ok((&token[..open], Some(&token[open + 1..token.len() - 1])))
+}
+
+impl Filter {
+    fn parse(raw: &str) -> Result<Filter, QueryError> {
+        let raw = raw.trim();
+        if let Some(rest) = raw.strip_prefix('=') {
+            return Ok(Filter::Equals(parse_literal(rest)?));
+        }
+        if let Some(rest) = raw.strip_prefix('~') {
+            let pattern = parse_literal(rest)?;
+            let re = Regex::new(&pattern)
+                .map_err(|e| QueryError::Syntax(format!("bad regex '{}': {}", pattern, e)))?;
+            return Ok(Filter::Matches(re));
+        }
+        if raw.starts_with(':') {
+            let mut types = Vec::new();
+            for part in raw.split('|') {
+                let name = part
+                    .trim()
+                    .strip_prefix(':')
+                    .ok_or_else(|| QueryError::Syntax(format!("bad type filter '{}'", raw)))?;
+                let ty = SchemaType::from_name(name)
+                    .ok_or_else(|| QueryError::Syntax(format!("unknown schema type '{}'", name)))?;
+                types.push(ty);
+            }
+            return Ok(Filter::Type(types));
+        }
+        Err(QueryError::Syntax(format!("unrecognized filter '{}'", raw)))
+    }
+}
+
+fn parse_literal(s: &str) -> Result<String, QueryError> {
+    let s = s.trim();
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        Ok(s[1..s.len() - 1].to_string())
+    } else {
+        Err(QueryError::Syntax(format!(
+            "expected a quoted string literal, got '{}'",
+            s
+        )))
+    }
+}
+
+/// Error parsing a query string.
+#[derive(Debug)]
+pub enum QueryError {
+    Syntax(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Syntax(msg) => write!(f, "query syntax error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_str;
+
+    fn root(input: &str) -> HashMap<String, Value> {
+        parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn child_step_descends_by_key() {
+        let root = root("log.file = /var/log/console.log\n");
+        let got = query(&root, "log.file").unwrap();
+        assert_eq!(got, vec![&Value::String("/var/log/console.log".into())]);
+    }
+
+    #[test]
+    fn wildcard_yields_direct_children() {
+        let root = root("log.file = a.log\nlog.name = default.log\n");
+        let mut got: Vec<&Value> = query(&root, "log.*").unwrap();
+        got.sort_by_key(|v| match v {
+            Value::String(s) => s.clone(),
+            _ => String::new(),
+        });
+        assert_eq!(
+            got,
+            vec![
+                &Value::String("a.log".into()),
+                &Value::String("default.log".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn descendants_yields_nested_children() {
+        let root = root("log.file = a.log\nlog.rotate.max = 3\n");
+        let got = query(&root, "log.**").unwrap();
+        let strings: Vec<&str> = got
+            .iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s.as_str()),
+                Value::Map(_) => None,
+            })
+            .collect();
+        assert!(strings.contains(&"a.log"));
+        assert!(strings.contains(&"3"));
+    }
+
+    #[test]
+    fn filter_matches_regex_on_leaf() {
+        let root = root("log.file = a.log\nlog.name = default.txt\n");
+        let mut got: Vec<&str> = query(&root, r#"log.*[~ "\.log$"]"#)
+            .unwrap()
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s.as_str()),
+                Value::Map(_) => None,
+            })
+            .collect();
+        got.sort();
+        assert_eq!(got, vec!["a.log"]);
+    }
+
+    #[test]
+    fn filter_equals_matches_exact_leaf() {
+        let root = root("debug = true\nverbose = true\nquiet = false\n");
+        let got = query(&root, r#"*[= "true"]"#).unwrap();
+        assert_eq!(got.len(), 2);
+    }
+
+    #[test]
+    fn filter_type_matches_parseable_leaf() {
+        let root = root("retry = 3\nendpoint = localhost\n");
+        let got = query(&root, "*[:integer]").unwrap();
+        assert_eq!(got, vec![&Value::String("3".into())]);
+    }
+
+    #[test]
+    fn unknown_filter_type_is_a_syntax_error() {
+        let root = root("retry = 3\n");
+        assert!(query(&root, "*[:wat]").is_err());
+    }
+}
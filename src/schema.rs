@@ -1,39 +1,62 @@
 //! Schema validation for sysctl.conf-style configs.
 //!
-//! Schema files use the same grammar as sysctl.conf(5): `key = type` per line.
-//! Supported types: `string`, `bool`, `integer`, `float`.
-//! Dot notation is supported: `log.file = string`.
+//! Schema files use the same grammar as sysctl.conf(5): `key = <decl>` per
+//! line. Dot notation is supported: `log.file = string`. A declaration is a
+//! base type, optionally followed by a constraint, a required marker, and a
+//! default:
+//!
+//! ```text
+//! <type>[(constraint)] [!] [default <value>]
+//! ```
+//!
+//! - Base types: `string`, `bool`, `integer`, `float`, `enum`
+//! - `integer(1..=10)` / `float(0..=1)` — inclusive numeric range
+//! - `enum(fast,slow,off)` — value must be one of the listed tokens
+//! - a trailing `!` marks the key required (`endpoint = string!`)
+//! - `default <value>` supplies a value to fill in when the key is absent
 
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-use crate::{parse_str, Value};
+use crate::{parse_str, set_nested, Value};
 
-/// Expected type for a config key.
+/// Expected base type for a config key.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SchemaType {
     String,
     Bool,
     Integer,
     Float,
+    Enum,
 }
 
 impl SchemaType {
-    fn from_name(name: &str) -> Option<Self> {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
         match name.trim().to_lowercase().as_str() {
             "string" => Some(SchemaType::String),
             "bool" | "boolean" => Some(SchemaType::Bool),
             "integer" | "int" => Some(SchemaType::Integer),
             "float" | "number" => Some(SchemaType::Float),
+            "enum" => Some(SchemaType::Enum),
             _ => None,
         }
     }
 
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            SchemaType::String => "string",
+            SchemaType::Bool => "bool",
+            SchemaType::Integer => "integer",
+            SchemaType::Float => "float",
+            SchemaType::Enum => "enum",
+        }
+    }
+
     /// Returns true if the raw string is valid for this type.
-    fn check_value(&self, raw: &str) -> bool {
+    pub(crate) fn check_value(&self, raw: &str) -> bool {
         match self {
-            SchemaType::String => true,
+            SchemaType::String | SchemaType::Enum => true,
             SchemaType::Bool => {
                 let s = raw.trim().to_lowercase();
                 matches!(s.as_str(), "true" | "false" | "1" | "0" | "yes" | "no")
@@ -44,13 +67,46 @@ impl SchemaType {
     }
 }
 
-/// Parsed schema: dotted key path -> expected type.
-pub type Schema = HashMap<String, SchemaType>;
+/// A constraint narrowing the set of values a key accepts, beyond its base type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    None,
+    /// Inclusive numeric range, checked against the value parsed as `f64`.
+    Range { min: f64, max: f64 },
+    /// The value must equal one of these tokens exactly.
+    Enum(Vec<String>),
+}
+
+impl Constraint {
+    fn check(&self, raw: &str) -> bool {
+        match self {
+            Constraint::None => true,
+            Constraint::Range { min, max } => raw
+                .trim()
+                .parse::<f64>()
+                .map(|n| n >= *min && n <= *max)
+                .unwrap_or(false),
+            Constraint::Enum(values) => values.iter().any(|v| v == raw.trim()),
+        }
+    }
+}
+
+/// A fully parsed schema declaration for one key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaEntry {
+    pub ty: SchemaType,
+    pub constraint: Constraint,
+    pub required: bool,
+    pub default: Option<String>,
+}
+
+/// Parsed schema: dotted key path -> declaration.
+pub type Schema = HashMap<String, SchemaEntry>;
 
-/// Parses a schema string (same grammar as sysctl.conf: `key = type`).
-/// Returns a flat map of dotted paths to schema types.
+/// Parses a schema string (same grammar as sysctl.conf: `key = <decl>`).
+/// Returns a flat map of dotted paths to schema entries.
 pub fn parse_schema_str(input: &str) -> Result<Schema, SchemaParseError> {
-    let parsed = parse_str(input).map_err(|e| SchemaParseError::Parse(e))?;
+    let parsed = parse_str(input).map_err(SchemaParseError::Parse)?;
     flatten_to_schema(parsed)
 }
 
@@ -78,14 +134,21 @@ fn flatten_to_schema_impl(
             format!("{}.{}", prefix, k)
         };
         match v {
-            Value::String(type_name) => {
-                let schema_type = SchemaType::from_name(type_name).ok_or_else(|| {
-                    SchemaParseError::UnknownType {
+            Value::String(decl) => {
+                let (ty, constraint, required, default) = parse_decl(decl)
+                    .map_err(|message| SchemaParseError::InvalidDecl {
                         key: path.clone(),
-                        type_name: type_name.clone(),
-                    }
-                })?;
-                out.insert(path, schema_type);
+                        message,
+                    })?;
+                out.insert(
+                    path,
+                    SchemaEntry {
+                        ty,
+                        constraint,
+                        required,
+                        default,
+                    },
+                );
             }
             Value::Map(m) => flatten_to_schema_impl(m, &path, out)?,
         }
@@ -93,11 +156,106 @@ fn flatten_to_schema_impl(
     Ok(())
 }
 
-/// Validates a parsed config against a schema.
+/// Parses one declaration: `<type>[(constraint)] [!] [default <value>]`.
+fn parse_decl(decl: &str) -> Result<(SchemaType, Constraint, bool, Option<String>), String> {
+    let decl = decl.trim();
+
+    let type_end = decl
+        .find(|c: char| c == '(' || c == '!' || c.is_whitespace())
+        .unwrap_or(decl.len());
+    let type_name = &decl[..type_end];
+    let ty = SchemaType::from_name(type_name).ok_or_else(|| format!("unknown type '{}'", type_name))?;
+    let mut rest = decl[type_end..].trim_start();
+
+    let mut constraint = Constraint::None;
+    if let Some(stripped) = rest.strip_prefix('(') {
+        let close = stripped
+            .find(')')
+            .ok_or_else(|| format!("unterminated '(' in '{}'", decl))?;
+        constraint = parse_constraint(ty, &stripped[..close])?;
+        rest = stripped[close + 1..].trim_start();
+    } else if ty == SchemaType::Enum {
+        return Err(format!(
+            "'enum' requires an allowed-value list, e.g. 'enum(a,b,c)' in '{}'",
+            decl
+        ));
+    }
+
+    let mut required = false;
+    if let Some(stripped) = rest.strip_prefix('!') {
+        required = true;
+        rest = stripped.trim_start();
+    }
+
+    let mut default = None;
+    if let Some(stripped) = rest.strip_prefix("default") {
+        let lit = stripped.trim();
+        if lit.is_empty() {
+            return Err(format!("'default' with no value in '{}'", decl));
+        }
+        let lit = lit
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(lit);
+        if !ty.check_value(lit) {
+            return Err(format!("default '{}' is not a valid '{}'", lit, ty.name()));
+        }
+        if !constraint.check(lit) {
+            return Err(format!(
+                "default '{}' does not satisfy the constraint on '{}'",
+                lit, decl
+            ));
+        }
+        default = Some(lit.to_string());
+        rest = "";
+    }
+
+    if !rest.trim().is_empty() {
+        return Err(format!("unexpected trailing text '{}'", rest.trim()));
+    }
+
+    Ok((ty, constraint, required, default))
+}
+
+fn parse_constraint(ty: SchemaType, args: &str) -> Result<Constraint, String> {
+    match ty {
+        SchemaType::Integer | SchemaType::Float => {
+            let (min_str, max_str) = args.split_once("..=").ok_or_else(|| {
+                format!("expected an inclusive range like '1..=10', got '{}'", args)
+            })?;
+            let min: f64 = min_str
+                .trim()
+                .parse()
+                .map_err(|_| format!("bad range start '{}'", min_str.trim()))?;
+            let max: f64 = max_str
+                .trim()
+                .parse()
+                .map_err(|_| format!("bad range end '{}'", max_str.trim()))?;
+            Ok(Constraint::Range { min, max })
+        }
+        SchemaType::Enum => Ok(Constraint::Enum(
+            args.split(',').map(|s| s.trim().to_string()).collect(),
+        )),
+        SchemaType::String | SchemaType::Bool => {
+            Err(format!("type '{}' does not support constraints", ty.name()))
+        }
+    }
+}
+
+/// Validates a parsed config against a schema and returns an augmented copy
+/// of `config` with defaults filled in for absent, non-required keys.
 /// - Every key in config must be defined in schema.
-/// - Every value must parse as the schema type (string, bool, integer, float).
-pub fn validate(config: &HashMap<String, Value>, schema: &Schema) -> Result<(), SchemaValidationError> {
-    validate_impl(config, "", schema)
+/// - Every value must parse as its schema type and satisfy its constraint.
+/// - Every key marked required (`!`) must be present.
+pub fn validate(
+    config: &HashMap<String, Value>,
+    schema: &Schema,
+) -> Result<HashMap<String, Value>, SchemaValidationError> {
+    validate_impl(config, "", schema)?;
+    check_required(config, schema)?;
+    let mut augmented = config.clone();
+    fill_defaults(&mut augmented, schema);
+    Ok(augmented)
 }
 
 fn validate_impl(
@@ -113,22 +271,32 @@ fn validate_impl(
         };
         match v {
             Value::String(raw) => {
-                let expected = schema.get(&path).ok_or_else(|| SchemaValidationError::UnknownKey {
-                    key: path.clone(),
-                })?;
-                let expected_name = match expected {
-                    SchemaType::String => "string",
-                    SchemaType::Bool => "bool",
-                    SchemaType::Integer => "integer",
-                    SchemaType::Float => "float",
-                };
-                if !expected.check_value(raw) {
+                let entry = schema
+                    .get(&path)
+                    .ok_or_else(|| SchemaValidationError::UnknownKey { key: path.clone() })?;
+                if !entry.ty.check_value(raw) {
                     return Err(SchemaValidationError::InvalidType {
                         key: path,
-                        expected: expected_name.to_string(),
+                        expected: entry.ty.name().to_string(),
                         value: raw.clone(),
                     });
                 }
+                if !entry.constraint.check(raw) {
+                    return Err(match &entry.constraint {
+                        Constraint::None => unreachable!("None constraint always checks true"),
+                        Constraint::Range { min, max } => SchemaValidationError::OutOfRange {
+                            key: path,
+                            value: raw.clone(),
+                            min: *min,
+                            max: *max,
+                        },
+                        Constraint::Enum(values) => SchemaValidationError::NotInEnum {
+                            key: path,
+                            value: raw.clone(),
+                            allowed: values.clone(),
+                        },
+                    });
+                }
             }
             Value::Map(m) => validate_impl(m, &path, schema)?,
         }
@@ -136,18 +304,56 @@ fn validate_impl(
     Ok(())
 }
 
+fn check_required(config: &HashMap<String, Value>, schema: &Schema) -> Result<(), SchemaValidationError> {
+    for (path, entry) in schema {
+        if entry.required && get_by_path(config, path).is_none() {
+            return Err(SchemaValidationError::MissingKey { key: path.clone() });
+        }
+    }
+    Ok(())
+}
+
+fn fill_defaults(config: &mut HashMap<String, Value>, schema: &Schema) {
+    for (path, entry) in schema {
+        if entry.required {
+            continue;
+        }
+        let Some(default) = &entry.default else {
+            continue;
+        };
+        if get_by_path(config, path).is_none() {
+            set_nested(config, path, Value::String(default.clone()));
+        }
+    }
+}
+
+/// Looks up a dotted path in a (possibly nested) config map.
+fn get_by_path<'a>(config: &'a HashMap<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut current = config;
+    let mut last = parts.next()?;
+    for part in parts {
+        match current.get(last) {
+            Some(Value::Map(m)) => current = m,
+            _ => return None,
+        }
+        last = part;
+    }
+    current.get(last)
+}
+
 #[derive(Debug)]
 pub enum SchemaParseError {
     Parse(crate::ParseError),
-    UnknownType { key: String, type_name: String },
+    InvalidDecl { key: String, message: String },
 }
 
 impl std::fmt::Display for SchemaParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SchemaParseError::Parse(e) => write!(f, "parse: {}", e),
-            SchemaParseError::UnknownType { key, type_name } => {
-                write!(f, "schema key '{}': unknown type '{}'", key, type_name)
+            SchemaParseError::InvalidDecl { key, message } => {
+                write!(f, "schema key '{}': {}", key, message)
             }
         }
     }
@@ -174,12 +380,28 @@ impl std::error::Error for SchemaLoadError {}
 
 #[derive(Debug)]
 pub enum SchemaValidationError {
-    UnknownKey { key: String },
+    UnknownKey {
+        key: String,
+    },
     InvalidType {
         key: String,
         expected: String,
         value: String,
     },
+    OutOfRange {
+        key: String,
+        value: String,
+        min: f64,
+        max: f64,
+    },
+    NotInEnum {
+        key: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+    MissingKey {
+        key: String,
+    },
 }
 
 impl std::fmt::Display for SchemaValidationError {
@@ -197,6 +419,30 @@ impl std::fmt::Display for SchemaValidationError {
                 "validation error: key '{}' expected type '{}', got value '{}'",
                 key, expected, value
             ),
+            SchemaValidationError::OutOfRange {
+                key,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "validation error: key '{}' value '{}' is outside range {}..={}",
+                key, value, min, max
+            ),
+            SchemaValidationError::NotInEnum {
+                key,
+                value,
+                allowed,
+            } => write!(
+                f,
+                "validation error: key '{}' value '{}' is not one of [{}]",
+                key,
+                value,
+                allowed.join(", ")
+            ),
+            SchemaValidationError::MissingKey { key } => {
+                write!(f, "validation error: required key '{}' is missing", key)
+            }
         }
     }
 }
@@ -217,10 +463,10 @@ log.file = string
 retry = integer
 "#;
         let schema = parse_schema_str(input).unwrap();
-        assert_eq!(schema.get("endpoint"), Some(&SchemaType::String));
-        assert_eq!(schema.get("debug"), Some(&SchemaType::Bool));
-        assert_eq!(schema.get("log.file"), Some(&SchemaType::String));
-        assert_eq!(schema.get("retry"), Some(&SchemaType::Integer));
+        assert_eq!(schema.get("endpoint").unwrap().ty, SchemaType::String);
+        assert_eq!(schema.get("debug").unwrap().ty, SchemaType::Bool);
+        assert_eq!(schema.get("log.file").unwrap().ty, SchemaType::String);
+        assert_eq!(schema.get("retry").unwrap().ty, SchemaType::Integer);
     }
 
     #[test]
@@ -278,4 +524,91 @@ log.file = /var/log/console.log
             _ => panic!("expected InvalidType"),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn range_constraint_rejects_out_of_bounds() {
+        let schema = parse_schema_str("retry = integer(1..=10)\n").unwrap();
+        let ok = parse_str("retry = 5\n").unwrap();
+        assert!(validate(&ok, &schema).is_ok());
+
+        let too_big = parse_str("retry = 11\n").unwrap();
+        let err = validate(&too_big, &schema).unwrap_err();
+        match err {
+            SchemaValidationError::OutOfRange { min, max, .. } => {
+                assert_eq!(min, 1.0);
+                assert_eq!(max, 10.0);
+            }
+            _ => panic!("expected OutOfRange"),
+        }
+    }
+
+    #[test]
+    fn enum_constraint_rejects_unlisted_token() {
+        let schema = parse_schema_str("mode = enum(fast,slow,off)\n").unwrap();
+        let ok = parse_str("mode = slow\n").unwrap();
+        assert!(validate(&ok, &schema).is_ok());
+
+        let bad = parse_str("mode = turbo\n").unwrap();
+        let err = validate(&bad, &schema).unwrap_err();
+        match err {
+            SchemaValidationError::NotInEnum { allowed, .. } => {
+                assert_eq!(allowed, vec!["fast", "slow", "off"]);
+            }
+            _ => panic!("expected NotInEnum"),
+        }
+    }
+
+    #[test]
+    fn required_key_missing_is_an_error() {
+        let schema = parse_schema_str("endpoint = string!\ndebug = bool\n").unwrap();
+        let config = parse_str("debug = true\n").unwrap();
+        let err = validate(&config, &schema).unwrap_err();
+        match err {
+            SchemaValidationError::MissingKey { key } => assert_eq!(key, "endpoint"),
+            _ => panic!("expected MissingKey"),
+        }
+    }
+
+    #[test]
+    fn default_is_filled_in_when_absent() {
+        let schema = parse_schema_str("retry = integer default 3\nlog.file = string\n").unwrap();
+        let config = parse_str("log.file = /var/log/console.log\n").unwrap();
+        let augmented = validate(&config, &schema).unwrap();
+        assert_eq!(augmented.get("retry"), Some(&Value::String("3".into())));
+    }
+
+    #[test]
+    fn default_does_not_override_present_value() {
+        let schema = parse_schema_str("retry = integer default 3\n").unwrap();
+        let config = parse_str("retry = 7\n").unwrap();
+        let augmented = validate(&config, &schema).unwrap();
+        assert_eq!(augmented.get("retry"), Some(&Value::String("7".into())));
+    }
+
+    #[test]
+    fn default_outside_range_is_rejected() {
+        let err = parse_schema_str("retry = integer(1..=10) default 999\n").unwrap_err();
+        match err {
+            SchemaParseError::InvalidDecl { key, .. } => assert_eq!(key, "retry"),
+            _ => panic!("expected InvalidDecl"),
+        }
+    }
+
+    #[test]
+    fn default_not_matching_type_is_rejected() {
+        let err = parse_schema_str("debug = bool default notabool\n").unwrap_err();
+        match err {
+            SchemaParseError::InvalidDecl { key, .. } => assert_eq!(key, "debug"),
+            _ => panic!("expected InvalidDecl"),
+        }
+    }
+
+    #[test]
+    fn bare_enum_without_allowed_list_is_rejected() {
+        let err = parse_schema_str("mode = enum\n").unwrap_err();
+        match err {
+            SchemaParseError::InvalidDecl { key, .. } => assert_eq!(key, "mode"),
+            _ => panic!("expected InvalidDecl"),
+        }
+    }
+}